@@ -0,0 +1,68 @@
+use crate::{SendError, SendTimeoutError, SyncSender, TrySendError};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BudgetSendError<T> {
+    BudgetExhausted(T),
+    Disconnected(T),
+}
+
+/// A sender which bounds the total time spent blocked across all of its sends.
+#[derive(Debug)]
+pub struct BudgetSender<T> {
+    sender: SyncSender<T>,
+    remaining: Cell<Duration>,
+}
+
+impl<T> BudgetSender<T> {
+    pub(crate) fn new(sender: SyncSender<T>, total: Duration) -> BudgetSender<T> {
+        BudgetSender {
+            sender,
+            remaining: Cell::new(total),
+        }
+    }
+
+    /// Sends `value`, blocking for at most the remaining budget; the time
+    /// actually spent blocked is deducted from it.
+    pub fn send(&self, value: T) -> Result<(), BudgetSendError<T>> {
+        // Sends which don't need to block are free
+        let value = match self.sender.try_send(value) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(value)) => value,
+            Err(TrySendError::Disconnected(value)) => {
+                return Err(BudgetSendError::Disconnected(value))
+            }
+        };
+
+        let start = Instant::now();
+        let res = match start.checked_add(self.remaining.get()) {
+            Some(deadline) => self.sender.send_retry(value, deadline),
+            None => self
+                .sender
+                .send(value)
+                .map_err(|SendError(value)| SendTimeoutError::Disconnected(value)),
+        };
+        let blocked = start.elapsed();
+        self.remaining.set(
+            self.remaining
+                .get()
+                .checked_sub(blocked)
+                .unwrap_or_default(),
+        );
+
+        res.map_err(|err| match err {
+            SendTimeoutError::Timeout(value) => BudgetSendError::BudgetExhausted(value),
+            SendTimeoutError::Disconnected(value) => BudgetSendError::Disconnected(value),
+        })
+    }
+
+    /// The time this sender may still spend blocked.
+    pub fn remaining(&self) -> Duration {
+        self.remaining.get()
+    }
+
+    pub fn into_inner(self) -> SyncSender<T> {
+        self.sender
+    }
+}
@@ -1,12 +1,20 @@
 #![feature(optin_builtin_traits)]
 #![feature(checked_duration_since)]
 
+use std::collections::vec_deque;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+mod budget;
+mod merge;
 mod queue;
+mod recycle;
 mod rendezvous;
+mod router;
+mod spillover;
+mod timed;
 mod token;
+mod traced;
 
 #[cfg(test)]
 mod test;
@@ -14,6 +22,14 @@ mod test;
 use queue::Queue;
 use token::Token;
 
+pub use budget::{BudgetSendError, BudgetSender};
+pub use merge::merge_weighted;
+pub use recycle::{recycle_channel, RecycleReceiver, RecycleSender};
+pub use router::{router_channel, Router};
+pub use spillover::{spillover_channel, SpilloverSender};
+pub use timed::{timed_channel, TimedReceiver, TimedSender};
+pub use traced::{traced_channel, TracedReceiver, TracedSender};
+
 #[derive(Debug)]
 struct SenderInner<T> {
     inner: Arc<Queue<T>>,
@@ -26,6 +42,22 @@ pub enum TrySendError<T> {
     Disconnected(T),
 }
 
+impl<T> TrySendError<T> {
+    /// Transforms the value carried by this error, preserving the variant.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> TrySendError<U> {
+        match self {
+            TrySendError::Full(value) => TrySendError::Full(f(value)),
+            TrySendError::Disconnected(value) => TrySendError::Disconnected(f(value)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SendTimeoutError<T> {
+    Timeout(T),
+    Disconnected(T),
+}
+
 impl<T> SenderInner<T> {
     fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
         if !self.token.is_present() {
@@ -55,11 +87,37 @@ impl<T> SenderInner<T> {
         }
         Ok(())
     }
+
+    fn send_deadline(&self, mut value: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
+        loop {
+            match self.try_send(value) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(ret)) => {
+                    value = ret;
+                    // Note that this also returns (without timing out) if the
+                    // receiver goes away, so we'll notice that promptly.
+                    if self.token.wait_until(deadline) {
+                        return Err(SendTimeoutError::Timeout(value));
+                    }
+                }
+                Err(TrySendError::Disconnected(value)) => {
+                    return Err(SendTimeoutError::Disconnected(value));
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct SendError<T>(T);
 
+impl<T> SendError<T> {
+    /// Transforms the value carried by this error.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> SendError<U> {
+        SendError(f(self.0))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Sender<T>(Arc<SenderInner<T>>);
 
@@ -83,6 +141,8 @@ struct ReceiverInner<T> {
 pub enum TryRecvError {
     Empty,
     Disconnected,
+    /// The receiver is paused; see `Receiver::pause`.
+    Paused,
 }
 
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
@@ -102,10 +162,13 @@ impl From<RecvError> for RecvTimeoutError {
 impl<T> ReceiverInner<T> {
     fn recv(&self) -> Result<T, RecvError> {
         loop {
+            if self.token.is_stopped() {
+                return Err(RecvError);
+            }
             match self.try_recv() {
                 Ok(value) => return Ok(value),
                 Err(TryRecvError::Disconnected) => return Err(RecvError),
-                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Empty) | Err(TryRecvError::Paused) => {}
             }
             self.token.wait();
         }
@@ -113,10 +176,13 @@ impl<T> ReceiverInner<T> {
 
     fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
         loop {
+            if self.token.is_stopped() {
+                return Err(RecvTimeoutError::Disconnected);
+            }
             match self.try_recv() {
                 Ok(value) => return Ok(value),
                 Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
-                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Empty) | Err(TryRecvError::Paused) => {}
             }
             if self.token.wait_until(deadline) {
                 return Err(RecvTimeoutError::Timeout);
@@ -125,13 +191,20 @@ impl<T> ReceiverInner<T> {
     }
 
     fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.try_recv_status().map(|(value, _)| value)
+    }
+
+    fn try_recv_status(&self) -> Result<(T, bool), TryRecvError> {
+        if self.token.is_paused() {
+            return Err(TryRecvError::Paused);
+        }
         // If we check *after* popping then the sender may have placed data in the buffer and then
         // left, which would lead to an incorrect return of Disconnected, instead of Empty.
         let present = self.token.is_present();
         if let Some(value) = self.inner.pop() {
             // we've successfully read, so wake up the sender
             self.token.wake();
-            Ok(value)
+            Ok((value, present))
         } else {
             if present {
                 Err(TryRecvError::Empty)
@@ -194,6 +267,23 @@ impl<T> Iterator for TryIter<'_, T> {
     }
 }
 
+pub struct DrainIntoIter<T> {
+    inner: vec_deque::IntoIter<T>,
+}
+
+impl<T> Iterator for DrainIntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for DrainIntoIter<T> {}
+
 pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
     let inner = Arc::new(Queue::unbounded());
     let (sender, receiver) = token::tokens();
@@ -229,7 +319,35 @@ impl<T> SyncSender<T> {
     pub fn send(&self, value: T) -> Result<(), SendError<T>> {
         match &self.0 {
             SyncSenderInner::Normal(n) => n.send(value),
-            SyncSenderInner::Rendezvous(n) => n.send(value).map_err(SendError),
+            SyncSenderInner::Rendezvous(n) => n.send(value),
+        }
+    }
+
+    /// Wraps this sender such that, across all sends, it spends at most
+    /// `total` blocked on a full channel.
+    pub fn with_budget(self, total: Duration) -> BudgetSender<T> {
+        BudgetSender::new(self, total)
+    }
+
+    /// A snapshot of how many more values fit in the channel's buffer.
+    ///
+    /// This may be stale by the time it returns, so a subsequent `try_send`
+    /// can still fail with `Full`.
+    pub fn capacity_remaining(&self) -> usize {
+        match &self.0 {
+            SyncSenderInner::Normal(n) => n.inner.capacity_remaining().unwrap(),
+            SyncSenderInner::Rendezvous(_) => 0,
+        }
+    }
+
+    /// Retries sending while the channel is full, up until `deadline`.
+    ///
+    /// Unlike waiting out a timeout, this returns `Disconnected` as soon as the
+    /// receiver goes away.
+    pub fn send_retry(&self, value: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
+        match &self.0 {
+            SyncSenderInner::Normal(n) => n.send_deadline(value, deadline),
+            SyncSenderInner::Rendezvous(n) => n.send_deadline(value, deadline),
         }
     }
 }
@@ -249,6 +367,20 @@ impl<T> Receiver<T> {
         }
     }
 
+    /// Like `try_recv`, but also returns whether any senders were still
+    /// connected.
+    ///
+    /// This is observed before taking the value, so if it's `false` then
+    /// nothing will be added to the channel anymore, i.e., we're draining the
+    /// remaining buffer. If it's `true`, the senders may of course still
+    /// disconnect right afterwards.
+    pub fn try_recv_status(&self) -> Result<(T, bool), TryRecvError> {
+        match &self.0 {
+            Receiver_::Normal(n) => n.try_recv_status(),
+            Receiver_::Rendezvous(n) => n.try_recv_status(),
+        }
+    }
+
     pub fn recv(&self) -> Result<T, RecvError> {
         match &self.0 {
             Receiver_::Normal(n) => n.recv(),
@@ -257,11 +389,14 @@ impl<T> Receiver<T> {
     }
 
     pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        if self.is_stopped() {
+            return Err(RecvTimeoutError::Disconnected);
+        }
         // This is just an optimistic check to be slightly more efficient
         match self.try_recv() {
             Ok(item) => return Ok(item),
             Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
-            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Empty) | Err(TryRecvError::Paused) => {}
         }
 
         match Instant::now().checked_add(timeout) {
@@ -284,6 +419,100 @@ impl<T> Receiver<T> {
     pub fn iter(&self) -> Iter<'_, T> {
         self.into_iter()
     }
+
+    /// A snapshot of the number of buffered values; senders may add more
+    /// concurrently.
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Receiver_::Normal(n) => n.inner.len(),
+            Receiver_::Rendezvous(_) => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes everything currently buffered in the channel, locking it just
+    /// once, and disconnects.
+    ///
+    /// Values sent after this call are not included (and senders will see
+    /// the channel as disconnected).
+    pub fn into_drain(self) -> DrainIntoIter<T> {
+        let buffered = match &self.0 {
+            Receiver_::Normal(n) => n.inner.take_all(),
+            Receiver_::Rendezvous(n) => n.take_sent().into_iter().collect(),
+        };
+        DrainIntoIter {
+            inner: buffered.into_iter(),
+        }
+    }
+
+    /// Pauses delivery: while paused, `recv` blocks and `try_recv` returns
+    /// `Paused`, even if there are buffered values. Senders are unaffected,
+    /// i.e., they keep buffering as usual.
+    pub fn pause(&self) {
+        self.handle().pause();
+    }
+
+    /// Resumes delivery after `pause`, waking up a blocked `recv`.
+    pub fn resume(&self) {
+        self.handle().resume();
+    }
+
+    /// Stops this receiver: any current or future blocking `recv` (and so
+    /// `iter`) returns `Err(RecvError)`, even if senders are still connected.
+    /// Similarly, `recv_timeout` and `recv_deadline` return
+    /// `Err(RecvTimeoutError::Disconnected)`.
+    ///
+    /// This is permanent. Values still in the channel can be received with
+    /// `try_recv`.
+    pub fn request_stop(&self) {
+        self.handle().request_stop();
+    }
+
+    fn forward_wakeups(&self, to: token::Handle) {
+        match &self.0 {
+            Receiver_::Normal(n) => n.token.forward_wakeups(to),
+            Receiver_::Rendezvous(n) => n.forward_wakeups(to),
+        }
+    }
+
+    fn is_stopped(&self) -> bool {
+        match &self.0 {
+            Receiver_::Normal(n) => n.token.is_stopped(),
+            Receiver_::Rendezvous(n) => n.is_stopped(),
+        }
+    }
+
+    /// Returns a handle which can be used to control this receiver from other
+    /// threads, e.g., to stop a blocked `recv`.
+    pub fn handle(&self) -> ReceiverHandle {
+        match &self.0 {
+            Receiver_::Normal(n) => ReceiverHandle(n.token.handle()),
+            Receiver_::Rendezvous(n) => ReceiverHandle(n.handle()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReceiverHandle(token::Handle);
+
+impl ReceiverHandle {
+    /// See `Receiver::request_stop`.
+    pub fn request_stop(&self) {
+        self.0.stop();
+    }
+
+    /// See `Receiver::pause`.
+    pub fn pause(&self) {
+        self.0.pause();
+    }
+
+    /// See `Receiver::resume`.
+    pub fn resume(&self) {
+        self.0.resume();
+    }
 }
 
 #[derive(Debug)]
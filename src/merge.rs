@@ -0,0 +1,96 @@
+use crate::{channel, Receiver, TryRecvError};
+use std::thread;
+
+struct Input<T> {
+    receiver: Receiver<T>,
+    weight: i64,
+    current: i64,
+    pending: Option<T>,
+    connected: bool,
+}
+
+impl<T> Input<T> {
+    fn fill(&mut self) {
+        if self.pending.is_some() || !self.connected {
+            return;
+        }
+        match self.receiver.try_recv() {
+            Ok(value) => self.pending = Some(value),
+            Err(TryRecvError::Disconnected) => self.connected = false,
+            Err(TryRecvError::Empty) | Err(TryRecvError::Paused) => {}
+        }
+    }
+}
+
+/// Merges several receivers into one, forwarding from a background thread.
+///
+/// When several inputs have values ready, they're picked proportionally to
+/// their weight (using a smooth weighted round-robin, so a 2:1 weighting
+/// yields A, B, A, A, B, A, ...). The returned receiver disconnects once all
+/// inputs have disconnected and been drained.
+///
+/// While all inputs are idle the background thread is parked; it's woken up
+/// by activity on any input. It exits (dropping the inputs) once the returned
+/// receiver is dropped.
+///
+/// Panics if any of the weights are zero.
+pub fn merge_weighted<T: Send + 'static>(inputs: Vec<(Receiver<T>, u32)>) -> Receiver<T> {
+    assert!(
+        inputs.iter().all(|&(_, weight)| weight > 0),
+        "merge weights must be non-zero"
+    );
+    let mut inputs = inputs
+        .into_iter()
+        .map(|(receiver, weight)| Input {
+            receiver,
+            weight: i64::from(weight),
+            current: 0,
+            pending: None,
+            connected: true,
+        })
+        .collect::<Vec<_>>();
+    let (tx, rx) = channel();
+    // Activity on any of the inputs wakes up our (the output sender's) token,
+    // so that's all we need to wait on. Note that this also wakes up if the
+    // output receiver goes away.
+    let waker = tx.0.token.handle();
+    for input in &inputs {
+        input.receiver.forward_wakeups(waker.clone());
+    }
+    thread::spawn(move || loop {
+        inputs.iter_mut().for_each(Input::fill);
+
+        let total: i64 = inputs
+            .iter()
+            .filter(|input| input.pending.is_some())
+            .map(|input| input.weight)
+            .sum();
+        if total == 0 {
+            if !tx.0.token.is_present() {
+                // Nobody is listening anymore, and we won't find out through
+                // a failed send while the inputs are idle.
+                break;
+            }
+            if inputs.iter().all(|input| !input.connected) {
+                break;
+            }
+            tx.0.token.wait();
+            continue;
+        }
+
+        for input in inputs.iter_mut().filter(|input| input.pending.is_some()) {
+            input.current += input.weight;
+        }
+        let chosen = inputs
+            .iter_mut()
+            .filter(|input| input.pending.is_some())
+            .max_by_key(|input| input.current)
+            .unwrap();
+        chosen.current -= total;
+        if tx.send(chosen.pending.take().unwrap()).is_err() {
+            // Nobody is listening anymore
+            break;
+        }
+    });
+    rx
+}
@@ -1,10 +1,18 @@
 use std::collections::VecDeque;
+use std::mem;
 use std::sync::Mutex;
 
 #[derive(Debug)]
 pub struct Queue<T> {
     bounded: Option<usize>,
     v: Mutex<VecDeque<T>>,
+    // Overflow for bounded queues which should never reject a push.
+    //
+    // Invariant: this is only non-empty if `v` is full; whenever we pop from `v`
+    // we first move the oldest spilled item over, which keeps the overall order
+    // FIFO.
+    // Always locked after `v`.
+    spill: Option<Mutex<VecDeque<T>>>,
 }
 
 impl<T> Queue<T> {
@@ -12,6 +20,7 @@ impl<T> Queue<T> {
         Queue {
             bounded: None,
             v: Mutex::new(VecDeque::new()),
+            spill: None,
         }
     }
 
@@ -19,21 +28,91 @@ impl<T> Queue<T> {
         Queue {
             bounded: Some(capacity),
             v: Mutex::new(VecDeque::with_capacity(capacity)),
+            spill: None,
+        }
+    }
+
+    pub fn spillover(capacity: usize) -> Queue<T> {
+        Queue {
+            bounded: Some(capacity),
+            v: Mutex::new(VecDeque::with_capacity(capacity)),
+            spill: Some(Mutex::new(VecDeque::new())),
         }
     }
 
     pub fn push(&self, value: T) -> Result<(), T> {
+        self.push_if_room(value).map(|_| ())
+    }
+
+    /// Pushes `value` if there's room for it, returning the length of the queue
+    /// right after the push.
+    ///
+    /// This is the only way to do admission control: the capacity check and
+    /// the push happen under the same lock, unlike with `len` or
+    /// `capacity_remaining`, which may be stale by the time they return.
+    pub fn push_if_room(&self, value: T) -> Result<usize, T> {
         let mut buf = self.v.lock().unwrap();
         if let Some(max_buf) = self.bounded {
             if buf.len() >= max_buf {
-                return Err(value);
+                return match &self.spill {
+                    Some(spill) => {
+                        let mut spill = spill.lock().unwrap();
+                        spill.push_back(value);
+                        Ok(buf.len() + spill.len())
+                    }
+                    None => Err(value),
+                };
             }
         }
         buf.push_back(value);
-        Ok(())
+        Ok(buf.len())
     }
 
     pub fn pop(&self) -> Option<T> {
-        self.v.lock().unwrap().pop_front()
+        let mut buf = self.v.lock().unwrap();
+        if let Some(spill) = &self.spill {
+            // Move the oldest spilled item over before popping, rather than
+            // after, so that this also works if `v` is empty because the
+            // capacity is zero.
+            if let Some(spilled) = spill.lock().unwrap().pop_front() {
+                buf.push_back(spilled);
+            }
+        }
+        buf.pop_front()
+    }
+
+    /// A snapshot of the number of queued values (including spilled ones).
+    ///
+    /// Other threads may push or pop concurrently, so this must not be used
+    /// to decide whether a push will succeed; use `push_if_room` instead.
+    pub fn len(&self) -> usize {
+        let buf = self.v.lock().unwrap();
+        buf.len() + self.spill.as_ref().map_or(0, |s| s.lock().unwrap().len())
+    }
+
+    /// A snapshot of how many more values fit before the queue is full, or
+    /// `None` if it's unbounded. Like `len`, this may be stale immediately.
+    pub fn capacity_remaining(&self) -> Option<usize> {
+        let len = self.v.lock().unwrap().len();
+        self.bounded.map(|max_buf| max_buf.saturating_sub(len))
+    }
+
+    /// Takes all currently queued values, in order, in a single critical
+    /// section (spilled values are appended while still holding the lock).
+    pub fn take_all(&self) -> VecDeque<T> {
+        let mut buf = self.v.lock().unwrap();
+        let mut all = mem::take(&mut *buf);
+        if let Some(spill) = &self.spill {
+            all.append(&mut spill.lock().unwrap());
+        }
+        all
+    }
+
+    /// A snapshot of the number of spilled values; see `len`.
+    pub fn spilled(&self) -> usize {
+        match &self.spill {
+            Some(spill) => spill.lock().unwrap().len(),
+            None => 0,
+        }
     }
 }
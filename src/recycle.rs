@@ -0,0 +1,62 @@
+use crate::{channel, Receiver, RecvError, SendError, Sender, TryRecvError};
+
+/// The sending half of a `recycle_channel`, which can reuse allocations
+/// handed back by the receiver.
+#[derive(Debug)]
+pub struct RecycleSender<T> {
+    sender: Sender<T>,
+    pool: Receiver<T>,
+}
+
+impl<T> RecycleSender<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.sender.send(value)
+    }
+
+    /// Takes a previously recycled value out of the pool, if there are any.
+    ///
+    /// Callers should fall back to allocating a fresh value if this returns
+    /// `None`.
+    pub fn acquire(&self) -> Option<T> {
+        self.pool.try_recv().ok()
+    }
+}
+
+#[derive(Debug)]
+pub struct RecycleReceiver<T> {
+    receiver: Receiver<T>,
+    pool: Sender<T>,
+}
+
+impl<T> RecycleReceiver<T> {
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.receiver.recv()
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Returns a value to the sender's pool once it's been processed.
+    ///
+    /// The value is handed back as-is, so it should be cleared first. If the
+    /// sender is gone, the value is simply dropped.
+    pub fn recycle(&self, value: T) {
+        let _ = self.pool.send(value);
+    }
+}
+
+pub fn recycle_channel<T>() -> (RecycleSender<T>, RecycleReceiver<T>) {
+    let (sender, receiver) = channel();
+    let (pool_sender, pool_receiver) = channel();
+    (
+        RecycleSender {
+            sender,
+            pool: pool_receiver,
+        },
+        RecycleReceiver {
+            receiver,
+            pool: pool_sender,
+        },
+    )
+}
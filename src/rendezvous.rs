@@ -1,5 +1,5 @@
 use crate::token::{self, Token};
-use crate::{RecvError, RecvTimeoutError, TryRecvError, TrySendError};
+use crate::{RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError};
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
@@ -119,7 +119,7 @@ pub fn channel<T>() -> (Arc<Sender<T>>, Receiver<T>) {
 }
 
 impl<T> Sender<T> {
-    pub fn send(&self, mut value: T) -> Result<(), T> {
+    pub fn send(&self, mut value: T) -> Result<(), SendError<T>> {
         loop {
             self.inner
                 .state
@@ -132,7 +132,39 @@ impl<T> Sender<T> {
                     ret
                 }
                 Err(TrySendError::Disconnected(ret)) => {
-                    return Err(ret);
+                    return Err(SendError(ret));
+                }
+            }
+        }
+    }
+
+    pub fn send_deadline(
+        &self,
+        mut value: T,
+        deadline: Instant,
+    ) -> Result<(), SendTimeoutError<T>> {
+        loop {
+            self.inner
+                .state
+                .compare_and_swap(EMPTY, SENDER_AVAILABLE, Ordering::SeqCst);
+            value = match self.try_send(value) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(ret)) => {
+                    self.token.wake();
+                    if self.token.wait_until(deadline) {
+                        // Other senders may still be waiting, but they'll
+                        // re-announce themselves once the receiver wakes them.
+                        self.inner.state.compare_and_swap(
+                            SENDER_AVAILABLE,
+                            EMPTY,
+                            Ordering::SeqCst,
+                        );
+                        return Err(SendTimeoutError::Timeout(ret));
+                    }
+                    ret
+                }
+                Err(TrySendError::Disconnected(ret)) => {
+                    return Err(SendTimeoutError::Disconnected(ret));
                 }
             }
         }
@@ -147,6 +179,9 @@ impl<T> Sender<T> {
     }
 
     pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        if self.token.is_peer_paused() {
+            return Err(self.err(value));
+        }
         if !self.inner.sender_ready() {
             // The token might've gone away between the CAS above and the call
             // to `token.is_present()`, but because we know for sure that *this*
@@ -165,6 +200,14 @@ impl<T> Sender<T> {
             return Err(self.err(value));
         }
 
+        // The receiver may have been paused after announcing itself (and
+        // before it noticed and retracted that), so check again now that no
+        // one else can hand off a value.
+        if self.token.is_peer_paused() {
+            self.inner.state.store(BOTH_AVAILABLE, Ordering::SeqCst);
+            return Err(self.err(value));
+        }
+
         self.inner.put(value);
 
         // We've acquired the "lock" in the CAS so this should definitely be in the SENDING
@@ -197,8 +240,15 @@ impl<T> Receiver<T> {
     }
 
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        if self.token.is_paused() {
+            return Err(TryRecvError::Paused);
+        }
+        // Like for buffered channels, check for senders *before* looking at
+        // the state: otherwise a sender could complete the handoff and leave
+        // in between, and we'd incorrectly report Disconnected.
+        let err = self.err();
         if !self.inner.receiver_ready() {
-            return Err(self.err());
+            return Err(err);
         }
 
         // Normally, one would expect this to be a CAS to acquire the value from
@@ -206,7 +256,11 @@ impl<T> Receiver<T> {
         // uniquely observing this state (no senders can act in the SENT state).
         let value = self.inner.state.load(Ordering::SeqCst);
         if value != SENT {
-            return Err(self.err());
+            // A sender may be parked after announcing itself; make sure it
+            // notices that we're now available too, so the handoff can
+            // complete by the next time we look.
+            self.token.wake();
+            return Err(err);
         }
 
         match self.inner.take() {
@@ -221,8 +275,50 @@ impl<T> Receiver<T> {
         }
     }
 
+    /// Takes a value which a sender already handed off, if any, regardless of
+    /// whether we're paused.
+    pub fn take_sent(&self) -> Option<T> {
+        if self.inner.state.load(Ordering::SeqCst) != SENT {
+            return None;
+        }
+        let value = self.inner.take();
+        self.inner.state.store(EMPTY, Ordering::SeqCst);
+        value
+    }
+
+    pub fn try_recv_status(&self) -> Result<(T, bool), TryRecvError> {
+        let present = self.token.is_present();
+        self.try_recv().map(|value| (value, present))
+    }
+
+    pub fn forward_wakeups(&self, to: token::Handle) {
+        self.token.forward_wakeups(to);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.token.is_stopped()
+    }
+
+    pub fn handle(&self) -> token::Handle {
+        self.token.handle()
+    }
+
     pub fn recv(&self) -> Result<T, RecvError> {
         loop {
+            if self.token.is_stopped() {
+                self.inner
+                    .state
+                    .compare_and_swap(RECEIVER_AVAILABLE, EMPTY, Ordering::SeqCst);
+                return Err(RecvError);
+            }
+            if self.token.is_paused() {
+                // Make sure senders don't hand us anything while we're paused
+                self.inner
+                    .state
+                    .compare_and_swap(RECEIVER_AVAILABLE, EMPTY, Ordering::SeqCst);
+                self.token.wait();
+                continue;
+            }
             self.inner
                 .state
                 .compare_and_swap(EMPTY, RECEIVER_AVAILABLE, Ordering::SeqCst);
@@ -245,12 +341,29 @@ impl<T> Receiver<T> {
                         .compare_and_swap(RECEIVER_AVAILABLE, EMPTY, Ordering::SeqCst);
                     return Err(RecvError);
                 }
+                // We were paused in the meantime; handled above.
+                Err(TryRecvError::Paused) => {}
             }
         }
     }
 
     pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
         loop {
+            if self.token.is_stopped() {
+                self.inner
+                    .state
+                    .compare_and_swap(RECEIVER_AVAILABLE, EMPTY, Ordering::SeqCst);
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            if self.token.is_paused() {
+                self.inner
+                    .state
+                    .compare_and_swap(RECEIVER_AVAILABLE, EMPTY, Ordering::SeqCst);
+                if self.token.wait_until(deadline) {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+                continue;
+            }
             self.inner
                 .state
                 .compare_and_swap(EMPTY, RECEIVER_AVAILABLE, Ordering::SeqCst);
@@ -280,6 +393,7 @@ impl<T> Receiver<T> {
                         .compare_and_swap(RECEIVER_AVAILABLE, EMPTY, Ordering::SeqCst);
                     return Err(RecvTimeoutError::Disconnected);
                 }
+                Err(TryRecvError::Paused) => {}
             }
         }
     }
@@ -0,0 +1,55 @@
+use crate::{channel, Receiver, Sender};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Demultiplexes a stream of tagged messages into per-tag receivers.
+#[derive(Debug)]
+pub struct Router<K, T> {
+    routes: Arc<Mutex<HashMap<K, Sender<T>>>>,
+}
+
+impl<K: Eq + Hash, T> Router<K, T> {
+    /// Returns a receiver for all messages tagged with `key` from now on.
+    ///
+    /// Subscribing to a key again replaces the previous subscription, which
+    /// then disconnects.
+    pub fn subscribe(&self, key: K) -> Receiver<T> {
+        let (tx, rx) = channel();
+        self.routes.lock().unwrap().insert(key, tx);
+        rx
+    }
+}
+
+/// Creates a channel of tagged messages which are routed, on a background
+/// thread, to the receiver subscribed to their tag.
+///
+/// Messages whose tag has no subscriber (at the time they're routed) are
+/// dropped. The subscribed receivers disconnect once all senders are gone.
+pub fn router_channel<K, T>() -> (Sender<(K, T)>, Router<K, T>)
+where
+    K: Eq + Hash + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = channel::<(K, T)>();
+    let routes = Arc::new(Mutex::new(HashMap::<K, Sender<T>>::new()));
+    let router = Router {
+        routes: routes.clone(),
+    };
+    thread::spawn(move || {
+        for (key, value) in rx {
+            let mut routes = routes.lock().unwrap();
+            let disconnected = match routes.get(&key) {
+                Some(route) => route.send(value).is_err(),
+                None => false,
+            };
+            if disconnected {
+                routes.remove(&key);
+            }
+        }
+        // Disconnect all subscribers, even if the router itself is still around.
+        routes.lock().unwrap().clear();
+    });
+    (tx, router)
+}
@@ -0,0 +1,43 @@
+use crate::queue::Queue;
+use crate::token;
+use crate::{Receiver, ReceiverInner, Receiver_, SendError, SenderInner, TrySendError};
+use std::sync::Arc;
+
+/// A sender which prefers to stay within the channel's capacity, but never
+/// blocks: once the channel is full, values are placed into an unbounded
+/// spillover buffer, which the receiver drains after the primary buffer.
+#[derive(Debug, Clone)]
+pub struct SpilloverSender<T>(Arc<SenderInner<T>>);
+
+impl<T> SpilloverSender<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.0.try_send(value) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Disconnected(value)) => Err(SendError(value)),
+            Err(TrySendError::Full(_)) => unreachable!("spillover queue is never full"),
+        }
+    }
+
+    /// The number of values currently held in the spillover buffer, i.e., how
+    /// far past the capacity the channel is.
+    ///
+    /// This is only a snapshot, as the receiver may be draining concurrently.
+    pub fn spilled_count(&self) -> usize {
+        self.0.inner.spilled()
+    }
+}
+
+pub fn spillover_channel<T>(capacity: usize) -> (SpilloverSender<T>, Receiver<T>) {
+    let inner = Arc::new(Queue::spillover(capacity));
+    let (sender, receiver) = token::tokens();
+    (
+        SpilloverSender(Arc::new(SenderInner {
+            inner: inner.clone(),
+            token: sender,
+        })),
+        Receiver(Receiver_::Normal(ReceiverInner {
+            inner,
+            token: receiver,
+        })),
+    )
+}
@@ -1511,3 +1511,697 @@ mod sync {
         }
     }
 }
+
+#[test]
+fn timed_reports_queue_latency() {
+    let (tx, rx) = timed_channel();
+    tx.send(1).unwrap();
+    // The consumer is "paused" here, so the message just sits in the queue
+    thread::sleep(Duration::from_millis(200));
+    let (value, waited) = rx.recv().unwrap();
+    assert_eq!(value, 1);
+    assert!(waited >= Duration::from_millis(200), "{:?}", waited);
+
+    tx.send(2).unwrap();
+    let (value, waited) = rx.recv().unwrap();
+    assert_eq!(value, 2);
+    assert!(waited < Duration::from_millis(200), "{:?}", waited);
+
+    drop(tx);
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn spillover_preserves_order() {
+    let (tx, rx) = spillover_channel(3);
+    for i in 0..3 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(tx.spilled_count(), 0);
+    for i in 3..8 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(tx.spilled_count(), 5);
+
+    assert_eq!(rx.recv(), Ok(0));
+    assert_eq!(rx.recv(), Ok(1));
+    assert_eq!(tx.spilled_count(), 3);
+    // Even though there's room in the primary buffer, this has to queue up
+    // behind the already spilled values.
+    tx.send(8).unwrap();
+
+    drop(tx);
+    assert_eq!(rx.iter().collect::<Vec<_>>(), (2..9).collect::<Vec<_>>());
+}
+
+#[test]
+fn traced_propagates_context() {
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TraceId(u64);
+
+    let (tx, mut rx) = traced_channel::<TraceId, &str>();
+    let entered = Arc::new(Mutex::new(Vec::new()));
+    let entered2 = entered.clone();
+    rx.on_recv(move |ctx, item| entered2.lock().unwrap().push((ctx.0, *item)));
+
+    let t = thread::spawn(move || {
+        tx.send(&TraceId(7), "first").unwrap();
+        tx.send(&TraceId(42), "second").unwrap();
+    });
+
+    assert_eq!(rx.recv(), Ok((TraceId(7), "first")));
+    assert_eq!(rx.recv(), Ok((TraceId(42), "second")));
+    t.join().unwrap();
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(*entered.lock().unwrap(), vec![(7, "first"), (42, "second")]);
+}
+
+#[test]
+fn send_retry_times_out_when_full() {
+    for &cap in &[0, 1] {
+        let (tx, _rx) = sync_channel(cap);
+        if cap > 0 {
+            tx.send(0).unwrap();
+        }
+        let deadline = Instant::now() + Duration::from_millis(100);
+        assert_eq!(
+            tx.send_retry(1, deadline),
+            Err(SendTimeoutError::Timeout(1))
+        );
+        assert!(Instant::now() >= deadline);
+    }
+}
+
+#[test]
+fn send_retry_disconnect_short_circuits() {
+    for &cap in &[0, 1] {
+        let (tx, rx) = sync_channel(cap);
+        if cap > 0 {
+            tx.send(0).unwrap();
+        }
+        let t = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            drop(rx);
+        });
+        let start = Instant::now();
+        let res = tx.send_retry(1, start + Duration::from_secs(30));
+        assert_eq!(res, Err(SendTimeoutError::Disconnected(1)));
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "{:?}",
+            start.elapsed()
+        );
+        t.join().unwrap();
+    }
+}
+
+#[test]
+fn send_retry_succeeds_once_room() {
+    for &cap in &[0, 1] {
+        let (tx, rx) = sync_channel(cap);
+        if cap > 0 {
+            tx.send(0).unwrap();
+        }
+        let t = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            rx.iter().collect::<Vec<_>>()
+        });
+        let deadline = Instant::now() + Duration::from_secs(30);
+        assert_eq!(tx.send_retry(1, deadline), Ok(()));
+        drop(tx);
+        let expected = if cap > 0 { vec![0, 1] } else { vec![1] };
+        assert_eq!(t.join().unwrap(), expected);
+    }
+}
+
+#[test]
+fn recycle_reuses_buffers() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug)]
+    struct Buf(Vec<u8>);
+
+    impl Buf {
+        fn new() -> Buf {
+            ALLOCATED.fetch_add(1, Ordering::SeqCst);
+            Buf(Vec::with_capacity(64))
+        }
+    }
+
+    let (tx, rx) = recycle_channel::<Buf>();
+    let t = thread::spawn(move || {
+        let mut sum = 0;
+        while let Ok(mut buf) = rx.recv() {
+            sum += buf.0.iter().map(|&b| b as usize).sum::<usize>();
+            buf.0.clear();
+            rx.recycle(buf);
+        }
+        sum
+    });
+
+    for i in 0..1000 {
+        // Wait for the previous buffer to come back so that, after the first
+        // send, we're always in the steady state.
+        let mut buf = if i == 0 {
+            Buf::new()
+        } else {
+            loop {
+                if let Some(buf) = tx.acquire() {
+                    break buf;
+                }
+                thread::yield_now();
+            }
+        };
+        assert!(buf.0.is_empty());
+        buf.0.push(1);
+        tx.send(buf).unwrap();
+    }
+    drop(tx);
+
+    assert_eq!(t.join().unwrap(), 1000);
+    assert_eq!(ALLOCATED.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn merge_weighted_ratio() {
+    const N: usize = 3000;
+    let (tx_a, rx_a) = channel();
+    let (tx_b, rx_b) = channel();
+    // Keep both inputs saturated for the part of the output we look at
+    for _ in 0..N {
+        tx_a.send('a').unwrap();
+        tx_b.send('b').unwrap();
+    }
+    drop(tx_a);
+    drop(tx_b);
+
+    let rx = merge_weighted(vec![(rx_a, 2), (rx_b, 1)]);
+    let sample = rx.iter().take(N).collect::<Vec<_>>();
+    let a = sample.iter().filter(|&&c| c == 'a').count();
+    let ratio = a as f64 / (N - a) as f64;
+    assert!(ratio > 1.9 && ratio < 2.1, "ratio: {}", ratio);
+
+    // Everything else still makes it through, then we disconnect
+    assert_eq!(rx.iter().count(), N);
+}
+
+#[test]
+fn merge_weighted_single_ready() {
+    let (tx_a, rx_a) = channel();
+    let (tx_b, rx_b) = channel::<i32>();
+    let rx = merge_weighted(vec![(rx_a, 1), (rx_b, 100)]);
+    for i in 0..10 {
+        tx_a.send(i).unwrap();
+    }
+    drop(tx_a);
+    assert_eq!(
+        rx.iter().take(10).collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+    drop(tx_b);
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn request_stop_breaks_iter() {
+    fn check(rx: Receiver<i32>, expected: Vec<i32>) {
+        let handle = rx.handle();
+        let (ack_tx, ack_rx) = channel();
+        let t = thread::spawn(move || {
+            let mut received = Vec::new();
+            for value in rx.iter() {
+                received.push(value);
+                ack_tx.send(()).unwrap();
+            }
+            received
+        });
+        // Only stop once everything buffered has been received, so the
+        // iterator is (about to be) blocked with nothing left to yield.
+        for _ in &expected {
+            ack_rx.recv().unwrap();
+        }
+        handle.request_stop();
+        assert_eq!(t.join().unwrap(), expected);
+    }
+
+    // In all cases the sender is alive (just idle) throughout
+    let (tx, rx) = channel();
+    tx.send(1).unwrap();
+    check(rx, vec![1]);
+    drop(tx);
+
+    let (tx, rx) = sync_channel(1);
+    tx.send(1).unwrap();
+    check(rx, vec![1]);
+    drop(tx);
+
+    let (tx, rx) = sync_channel(0);
+    check(rx, vec![]);
+    drop(tx);
+}
+
+#[test]
+fn request_stop_before_recv() {
+    let (tx, rx) = channel();
+    tx.send(1).unwrap();
+    rx.request_stop();
+    assert_eq!(rx.recv(), Err(RecvError));
+    assert_eq!(rx.try_recv(), Ok(1));
+}
+
+#[test]
+fn send_error_map() {
+    let (tx, _) = channel();
+    let err = tx.send(1).unwrap_err();
+    assert_eq!(err.map(|v| (v, "envelope")), SendError((1, "envelope")));
+}
+
+#[test]
+fn try_send_error_map() {
+    let full: TrySendError<i32> = TrySendError::Full(1);
+    assert_eq!(
+        full.map(|v| v.to_string()),
+        TrySendError::Full("1".to_string())
+    );
+    let disconnected: TrySendError<i32> = TrySendError::Disconnected(2);
+    assert_eq!(
+        disconnected.map(|v| v.to_string()),
+        TrySendError::Disconnected("2".to_string())
+    );
+}
+
+#[test]
+fn delay_still_receives_under_load() {
+    const SENDERS: usize = 4;
+    const PER_SENDER: usize = 100;
+
+    // A tiny xorshift so that senders don't all sleep in lockstep
+    fn delay(state: &mut u32) -> Duration {
+        *state ^= *state << 13;
+        *state ^= *state >> 17;
+        *state ^= *state << 5;
+        Duration::from_micros(u64::from(*state % 2000))
+    }
+
+    fn run<S: Clone + Send + 'static>(tx: S, rx: Receiver<usize>, send: fn(&S, usize)) {
+        let mut threads = Vec::new();
+        for id in 0..SENDERS {
+            let tx = tx.clone();
+            threads.push(thread::spawn(move || {
+                let mut state = id as u32 + 1;
+                for i in 0..PER_SENDER {
+                    thread::sleep(delay(&mut state));
+                    send(&tx, id * PER_SENDER + i);
+                }
+            }));
+        }
+        drop(tx);
+
+        let mut seen = vec![false; SENDERS * PER_SENDER];
+        for _ in 0..SENDERS * PER_SENDER {
+            let value = rx.recv().unwrap();
+            assert!(!seen[value], "received {} twice", value);
+            seen[value] = true;
+        }
+        assert_eq!(rx.recv(), Err(RecvError));
+        for t in threads {
+            t.join().unwrap();
+        }
+    }
+
+    for _ in 0..stress_factor() {
+        let (tx, rx) = channel();
+        run(tx, rx, |tx, v| tx.send(v).unwrap());
+        for &cap in &[0, 1, 2] {
+            let (tx, rx) = sync_channel(cap);
+            run(tx, rx, |tx, v| tx.send(v).unwrap());
+        }
+    }
+}
+
+#[test]
+fn router_demultiplexes() {
+    let (tx, router) = router_channel();
+    let evens = router.subscribe("even");
+    let odds = router.subscribe("odd");
+
+    for i in 0..100 {
+        let key = match i % 3 {
+            0 => "unrouted",
+            _ if i % 2 == 0 => "even",
+            _ => "odd",
+        };
+        tx.send((key, i)).unwrap();
+    }
+    drop(tx);
+
+    let evens = evens.iter().collect::<Vec<_>>();
+    let odds = odds.iter().collect::<Vec<_>>();
+    let expected = |rem| {
+        (0..100)
+            .filter(|i| i % 3 != 0 && i % 2 == rem)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(evens, expected(0));
+    assert_eq!(odds, expected(1));
+}
+
+#[test]
+fn queue_push_if_room_admission() {
+    use crate::queue::Queue;
+    use std::sync::Barrier;
+
+    const CAP: usize = 4;
+    const THREADS: usize = 8;
+
+    for _ in 0..100 {
+        let queue = Arc::new(Queue::bounded(CAP));
+        let barrier = Arc::new(Barrier::new(THREADS));
+        let threads = (0..THREADS)
+            .map(|i| {
+                let queue = queue.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    // Every thread sees room before anyone pushes...
+                    let saw_room = queue.len() < CAP;
+                    barrier.wait();
+                    // ...but only the atomic check-and-push decides admission.
+                    (saw_room, queue.push_if_room(i))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut lens = Vec::new();
+        let mut stale = 0;
+        for t in threads {
+            match t.join().unwrap() {
+                (_, Ok(len)) => lens.push(len),
+                (true, Err(_)) => stale += 1,
+                (false, Err(_)) => {}
+            }
+        }
+        // `len` said there was room for everyone, yet only `CAP` pushes were
+        // admitted, each observing a distinct post-push length.
+        assert_eq!(stale, THREADS - CAP);
+        lens.sort();
+        assert_eq!(lens, (1..=CAP).collect::<Vec<_>>());
+        assert_eq!(queue.len(), CAP);
+    }
+}
+
+#[test]
+fn len_and_capacity_remaining() {
+    let (tx, rx) = sync_channel(3);
+    assert!(rx.is_empty());
+    assert_eq!(tx.capacity_remaining(), 3);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(rx.len(), 2);
+    assert_eq!(tx.capacity_remaining(), 1);
+    rx.recv().unwrap();
+    assert_eq!(rx.len(), 1);
+    assert_eq!(tx.capacity_remaining(), 2);
+
+    let (tx, rx) = sync_channel::<i32>(0);
+    assert_eq!(rx.len(), 0);
+    assert_eq!(tx.capacity_remaining(), 0);
+}
+
+#[test]
+fn pause_blocks_recv_until_resume() {
+    let (tx, rx) = sync_channel(3);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    rx.pause();
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Paused));
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(50)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    // Sends still buffer while paused
+    tx.send(3).unwrap();
+    drop(tx);
+
+    let handle = rx.handle();
+    let (done_tx, done_rx) = channel();
+    let t = thread::spawn(move || {
+        let received = rx.iter().collect::<Vec<_>>();
+        done_tx.send(()).unwrap();
+        received
+    });
+
+    thread::sleep(Duration::from_millis(100));
+    // Still blocked, even though there's buffered data and no senders left
+    assert_eq!(done_rx.try_recv(), Err(TryRecvError::Empty));
+    handle.resume();
+    assert_eq!(t.join().unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn pause_rendezvous() {
+    let (tx, rx) = sync_channel(0);
+    rx.pause();
+    let handle = rx.handle();
+    let t = thread::spawn(move || rx.recv());
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(tx.try_send(1), Err(TrySendError::Full(1)));
+    handle.resume();
+    tx.send(2).unwrap();
+    assert_eq!(t.join().unwrap(), Ok(2));
+}
+
+#[test]
+fn into_drain_yields_buffer() {
+    let (tx, rx) = sync_channel(10_000);
+    for i in 0..10_000 {
+        tx.send(i).unwrap();
+    }
+    // The buffer is swapped out under a single lock acquisition; iterating
+    // then only touches the (owned) swapped-out buffer.
+    let drain = rx.into_drain();
+    assert_eq!(drain.len(), 10_000);
+    // Anything sent afterwards is not part of the drain
+    assert_eq!(tx.send(10_000), Err(SendError(10_000)));
+    assert_eq!(drain.collect::<Vec<_>>(), (0..10_000).collect::<Vec<_>>());
+}
+
+#[test]
+fn into_drain_spillover() {
+    let (tx, rx) = spillover_channel(2);
+    for i in 0..5 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(rx.into_drain().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn rendezvous_send_disconnect_error() {
+    // Blocked in send when the receiver goes away
+    let (tx, rx) = sync_channel(0);
+    let t = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        drop(rx);
+    });
+    assert_eq!(tx.send(1), Err(SendError(1)));
+    t.join().unwrap();
+
+    // Same error as the buffered path
+    for &cap in &[0, 1] {
+        let (tx, rx) = sync_channel(cap);
+        drop(rx);
+        assert_eq!(tx.send(2), Err(SendError(2)));
+        assert_eq!(tx.try_send(3), Err(TrySendError::Disconnected(3)));
+    }
+}
+
+#[test]
+fn try_recv_status_drain_down() {
+    let (tx, rx) = sync_channel(3);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(rx.try_recv_status(), Ok((1, true)));
+    tx.send(3).unwrap();
+    drop(tx);
+    assert_eq!(rx.try_recv_status(), Ok((2, false)));
+    assert_eq!(rx.try_recv_status(), Ok((3, false)));
+    assert_eq!(rx.try_recv_status(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn budget_caps_total_blocking() {
+    let (tx, rx) = sync_channel(1);
+    tx.send(0).unwrap();
+    let tx = tx.with_budget(Duration::from_millis(300));
+
+    let t = thread::spawn(move || {
+        // Make room for exactly one more value after a while, then stall
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(rx.recv(), Ok(0));
+        thread::sleep(Duration::from_secs(2));
+        rx
+    });
+
+    let start = Instant::now();
+    assert_eq!(tx.send(1), Ok(()));
+    assert!(tx.remaining() < Duration::from_millis(300));
+    assert_eq!(tx.send(2), Err(BudgetSendError::BudgetExhausted(2)));
+    assert_eq!(tx.remaining(), Duration::from_secs(0));
+    assert_eq!(tx.send(3), Err(BudgetSendError::BudgetExhausted(3)));
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(300), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(1), "{:?}", elapsed);
+
+    let rx = t.join().unwrap();
+    assert_eq!(rx.recv(), Ok(1));
+    drop(rx);
+    assert_eq!(tx.send(4), Err(BudgetSendError::Disconnected(4)));
+}
+
+#[test]
+fn spillover_zero_capacity() {
+    let (tx, rx) = spillover_channel(0);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(tx.spilled_count(), 2);
+    assert_eq!(rx.try_recv(), Ok(1));
+    drop(tx);
+    assert_eq!(rx.recv(), Ok(2));
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn merge_weighted_exits_when_output_dropped() {
+    // A full, paused input stays connected but never yields anything, and
+    // lets us observe (via `try_send`) when the merge thread drops it.
+    let (tx, input) = sync_channel(1);
+    tx.send(0).unwrap();
+    input.pause();
+
+    let rx = merge_weighted(vec![(input, 1)]);
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(tx.try_send(1), Err(TrySendError::Full(1)));
+
+    drop(rx);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        match tx.try_send(1) {
+            Err(TrySendError::Disconnected(_)) => break,
+            Err(TrySendError::Full(_)) => {}
+            Ok(()) => panic!("paused input was drained"),
+        }
+        assert!(Instant::now() < deadline, "merge thread did not exit");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn request_stop_breaks_recv_timeout() {
+    fn check(rx: Receiver<i32>) {
+        let handle = rx.handle();
+        let t = thread::spawn(move || {
+            let start = Instant::now();
+            let res = rx.recv_timeout(Duration::from_secs(30));
+            (res, start.elapsed(), rx)
+        });
+        thread::sleep(Duration::from_millis(100));
+        handle.request_stop();
+        let (res, elapsed, rx) = t.join().unwrap();
+        assert_eq!(res, Err(RecvTimeoutError::Disconnected));
+        assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+        // Also applies to calls made after stopping
+        assert_eq!(
+            rx.recv_deadline(Instant::now() + Duration::from_secs(30)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    // The senders stay alive (and idle) throughout
+    let (tx, rx) = channel();
+    check(rx);
+    drop(tx);
+
+    let (tx, rx) = sync_channel(0);
+    check(rx);
+    drop(tx);
+}
+
+#[test]
+fn merge_weighted_wakes_on_any_input() {
+    let (tx_a, rx_a) = channel();
+    let (tx_b, rx_b) = sync_channel(0);
+    let rx = merge_weighted(vec![(rx_a, 1), (rx_b, 1)]);
+
+    // Only the second (rendezvous) input sees any activity, long after the
+    // merge thread has gone idle.
+    thread::sleep(Duration::from_millis(50));
+    let t = thread::spawn(move || {
+        for i in 0..10 {
+            tx_b.send(i).unwrap();
+        }
+    });
+    assert_eq!(
+        rx.iter().take(10).collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+    t.join().unwrap();
+
+    drop(tx_a);
+    assert_eq!(rx.recv(), Err(RecvError));
+}
+
+#[test]
+fn pause_rendezvous_while_blocked() {
+    let (tx, rx) = sync_channel(0);
+    let handle = rx.handle();
+    let t = thread::spawn(move || rx.recv());
+    // Give the receiver time to park in recv, announcing itself to senders
+    thread::sleep(Duration::from_millis(100));
+    handle.pause();
+    for _ in 0..100 {
+        assert_eq!(tx.try_send(1), Err(TrySendError::Full(1)));
+    }
+    handle.resume();
+    tx.send(2).unwrap();
+    assert_eq!(t.join().unwrap(), Ok(2));
+}
+
+#[test]
+fn into_drain_paused() {
+    let (tx, rx) = sync_channel(3);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    rx.pause();
+    assert_eq!(rx.into_drain().collect::<Vec<_>>(), vec![1, 2]);
+
+    // Get a rendezvous sender to complete its handoff without us taking the
+    // value: once it has announced itself, `try_recv` lets it proceed.
+    loop {
+        let (tx, rx) = sync_channel(0);
+        let (done_tx, done_rx) = channel();
+        let t = thread::spawn(move || {
+            tx.send(1).unwrap();
+            done_tx.send(()).unwrap();
+        });
+        let handed_off = loop {
+            match rx.try_recv() {
+                Err(TryRecvError::Empty) => {}
+                // The handoff completed before we could stop looking; retry
+                Ok(1) => break false,
+                other => panic!("unexpected {:?}", other),
+            }
+            if done_rx.recv_timeout(Duration::from_millis(100)).is_ok() {
+                break true;
+            }
+        };
+        t.join().unwrap();
+        if handed_off {
+            rx.pause();
+            assert_eq!(rx.into_drain().collect::<Vec<_>>(), vec![1]);
+            break;
+        }
+    }
+}
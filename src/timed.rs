@@ -0,0 +1,37 @@
+use crate::{channel, Receiver, RecvError, SendError, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct TimedSender<T>(Sender<(Instant, T)>);
+
+impl<T> TimedSender<T> {
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.0
+            .send((Instant::now(), value))
+            .map_err(|err| err.map(|(_, value)| value))
+    }
+}
+
+#[derive(Debug)]
+pub struct TimedReceiver<T>(Receiver<(Instant, T)>);
+
+impl<T> TimedReceiver<T> {
+    /// Returns the received value along with the time it spent in the queue,
+    /// i.e., between the call to `send` and it being dequeued here.
+    pub fn recv(&self) -> Result<(T, Duration), RecvError> {
+        self.0.recv().map(elapsed)
+    }
+
+    pub fn try_recv(&self) -> Result<(T, Duration), TryRecvError> {
+        self.0.try_recv().map(elapsed)
+    }
+}
+
+fn elapsed<T>((enqueued, value): (Instant, T)) -> (T, Duration) {
+    (value, Instant::now().duration_since(enqueued))
+}
+
+pub fn timed_channel<T>() -> (TimedSender<T>, TimedReceiver<T>) {
+    let (sender, receiver) = channel();
+    (TimedSender(sender), TimedReceiver(receiver))
+}
@@ -5,8 +5,24 @@ use std::time::Instant;
 #[derive(Debug)]
 struct Inner {
     is_present: AtomicBool,
+    stopped: AtomicBool,
+    paused: AtomicBool,
     woke: Mutex<bool>,
     condvar: Condvar,
+    // Another waiter to wake up alongside this one; see `Token::forward_wakeups`.
+    // `forwarding` lets the common case skip locking `forward`.
+    forwarding: AtomicBool,
+    forward: Mutex<Option<Handle>>,
+}
+
+impl Inner {
+    fn wake_forward(&self) {
+        if self.forwarding.load(Ordering::SeqCst) {
+            if let Some(handle) = &*self.forward.lock().unwrap() {
+                handle.wake();
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -30,6 +46,65 @@ impl Token {
     pub fn wait_until(&self, deadline: Instant) -> bool {
         self.wait.wait_until(deadline)
     }
+
+    pub fn is_stopped(&self) -> bool {
+        self.wait.inner.stopped.load(Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.wait.is_paused()
+    }
+
+    /// Whether the other end (i.e., the one we wake up) is paused.
+    pub fn is_peer_paused(&self) -> bool {
+        self.signal.inner.paused.load(Ordering::SeqCst)
+    }
+
+    /// Returns a handle which can interrupt this token's waits, from any thread.
+    pub fn handle(&self) -> Handle {
+        Handle {
+            inner: self.wait.inner.clone(),
+        }
+    }
+
+    /// From now on, whenever this token would be woken up (or the other end
+    /// leaves), also wake up `to`. This lets a single thread wait on several
+    /// tokens at once.
+    pub fn forward_wakeups(&self, to: Handle) {
+        *self.wait.inner.forward.lock().unwrap() = Some(to);
+        self.wait.inner.forwarding.store(true, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Handle {
+    inner: Arc<Inner>,
+}
+
+impl Handle {
+    pub fn stop(&self) {
+        self.inner.stopped.store(true, Ordering::SeqCst);
+        self.wake();
+    }
+
+    pub fn pause(&self) {
+        self.inner.paused.store(true, Ordering::SeqCst);
+        // A blocked receiver may need to retract its availability
+        self.wake();
+    }
+
+    pub fn resume(&self) {
+        self.inner.paused.store(false, Ordering::SeqCst);
+        self.wake();
+    }
+
+    pub fn wake(&self) {
+        // Setting this under the lock (after updating the flags) means that
+        // the waiter either observes the flags before parking or is woken up.
+        *self.inner.woke.lock().unwrap() = true;
+        self.inner.condvar.notify_all();
+        self.inner.wake_forward();
+    }
 }
 
 impl Drop for Token {
@@ -56,8 +131,12 @@ pub fn tokens() -> (Token, Token) {
 fn make_token_pair() -> (SignalToken, WaitToken) {
     let token = Arc::new(Inner {
         is_present: AtomicBool::new(true),
+        stopped: AtomicBool::new(false),
+        paused: AtomicBool::new(false),
         woke: Mutex::new(false),
         condvar: Condvar::new(),
+        forwarding: AtomicBool::new(false),
+        forward: Mutex::new(None),
     });
     (
         SignalToken {
@@ -76,6 +155,7 @@ impl SignalToken {
     fn wake(&self) {
         *self.inner.woke.lock().unwrap() = true;
         self.inner.condvar.notify_one();
+        self.inner.wake_forward();
     }
 
     fn leave(&self) {
@@ -83,6 +163,7 @@ impl SignalToken {
         assert!(self.inner.is_present.swap(false, Ordering::SeqCst));
         // make sure to unblock all other threads if we've dropped
         self.inner.condvar.notify_all();
+        self.inner.wake_forward();
     }
 }
 
@@ -96,12 +177,19 @@ impl WaitToken {
         self.inner.is_present.load(Ordering::SeqCst)
     }
 
+    fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::SeqCst)
+    }
+
     fn wait(&self) {
         let mut woke = self.inner.woke.lock().unwrap();
         // This is a bit unusual in the sense that we're going to exit if either we've been woken
         // directly or the other end has disconnected. Note that the condvar is notified in both
         // wake() and leave()
-        while !*woke && self.is_present() {
+        //
+        // While paused we keep waiting even if the other end is gone, as otherwise the receiver
+        // would just spin; resuming wakes us up.
+        while !*woke && (self.is_present() || self.is_paused()) {
             woke = self.inner.condvar.wait(woke).unwrap();
         }
         *woke = false;
@@ -113,7 +201,7 @@ impl WaitToken {
         // directly or the other end has disconnected. Note that the condvar is notified in both
         // wake() and leave()
         let mut timed_out = false;
-        while !*woke && self.is_present() {
+        while !*woke && (self.is_present() || self.is_paused()) {
             let left = match deadline.checked_duration_since(Instant::now()) {
                 Some(v) => v,
                 // We've already gone past the deadline, so just exit
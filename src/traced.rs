@@ -0,0 +1,67 @@
+use crate::{channel, Receiver, RecvError, SendError, Sender, TryRecvError};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct TracedSender<C, T>(Sender<(C, T)>);
+
+impl<C: Clone + Send, T> TracedSender<C, T> {
+    /// Sends `value` along with a copy of the (producer's) current context.
+    pub fn send(&self, ctx: &C, value: T) -> Result<(), SendError<T>> {
+        self.0
+            .send((ctx.clone(), value))
+            .map_err(|err| err.map(|(_, value)| value))
+    }
+}
+
+type Hook<C, T> = Box<dyn Fn(&C, &T) + Send>;
+
+pub struct TracedReceiver<C, T> {
+    receiver: Receiver<(C, T)>,
+    on_recv: Option<Hook<C, T>>,
+}
+
+impl<C: fmt::Debug, T: fmt::Debug> fmt::Debug for TracedReceiver<C, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TracedReceiver")
+            .field("receiver", &self.receiver)
+            .finish()
+    }
+}
+
+impl<C: Clone + Send, T> TracedReceiver<C, T> {
+    /// Registers a hook which is called with the producer's context and the
+    /// item on every successful receive, before the item is returned (e.g., to
+    /// enter the producer's span).
+    pub fn on_recv<F>(&mut self, f: F)
+    where
+        F: Fn(&C, &T) + Send + 'static,
+    {
+        self.on_recv = Some(Box::new(f));
+    }
+
+    pub fn recv(&self) -> Result<(C, T), RecvError> {
+        self.receiver.recv().map(|item| self.received(item))
+    }
+
+    pub fn try_recv(&self) -> Result<(C, T), TryRecvError> {
+        self.receiver.try_recv().map(|item| self.received(item))
+    }
+
+    fn received(&self, (ctx, value): (C, T)) -> (C, T) {
+        if let Some(hook) = &self.on_recv {
+            hook(&ctx, &value);
+        }
+        (ctx, value)
+    }
+}
+
+pub fn traced_channel<C: Clone + Send, T>() -> (TracedSender<C, T>, TracedReceiver<C, T>) {
+    let (sender, receiver) = channel();
+    (
+        TracedSender(sender),
+        TracedReceiver {
+            receiver,
+            on_recv: None,
+        },
+    )
+}